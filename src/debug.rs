@@ -0,0 +1,116 @@
+use std::collections::{BTreeMap, HashSet};
+
+use crate::{parse, BfError, ByteRead, ByteWrite, Interpreter};
+
+/// Drives an `Interpreter` one instruction at a time, with breakpoints set on a
+/// source `[`/`]` position rather than on a (optimizer-dependent) instruction index.
+///
+/// Debugging always runs unoptimized code: the `SetZero`/`MultAdd` passes collapse
+/// whole loops into a single step, which defeats the point of single-stepping them.
+pub struct Debugger<R, W> {
+    interpreter: Interpreter<R, W>,
+    bracket_positions: BTreeMap<usize, usize>,
+    breakpoints: HashSet<usize>,
+    /// Set after `run_until_breakpoint` stops at a breakpoint, so the next call steps
+    /// past it instead of checking the same (unmoved) instruction pointer and returning
+    /// immediately. Left unset after construction and after plain `step` calls.
+    paused_at_breakpoint: bool,
+}
+
+impl<R: ByteRead, W: ByteWrite> Debugger<R, W> {
+    pub fn new(source: &str, input: R, output: W) -> Result<Debugger<R, W>, BfError> {
+        let (code, bracket_positions) = parse(source)?;
+        Ok(Debugger {
+            interpreter: Interpreter::new(code, input, output),
+            bracket_positions,
+            breakpoints: HashSet::new(),
+            paused_at_breakpoint: false,
+        })
+    }
+
+    /// Breaks just before executing the `[` or `]` at `source_pos` (a byte offset
+    /// into the source string originally passed to `new`).
+    pub fn break_at(&mut self, source_pos: usize) {
+        self.breakpoints.insert(source_pos);
+    }
+
+    pub fn clear_break_at(&mut self, source_pos: usize) {
+        self.breakpoints.remove(&source_pos);
+    }
+
+    pub fn instruction_pointer(&self) -> usize {
+        self.interpreter.instruction_pointer()
+    }
+
+    pub fn memory_pointer(&self) -> usize {
+        self.interpreter.memory_pointer()
+    }
+
+    /// The cells from `radius` before the memory pointer to `radius` after it, along
+    /// with the index of the first cell in the slice.
+    pub fn memory_window(&self, radius: usize) -> (usize, &[u32]) {
+        let memory = self.interpreter.memory();
+        let start = self.memory_pointer().saturating_sub(radius);
+        let end = (self.memory_pointer() + radius + 1).min(memory.len());
+        (start, &memory[start..end])
+    }
+
+    pub fn set_memory_pointer(&mut self, absolute: usize) -> Result<(), BfError> {
+        self.interpreter.set_memory_pointer(absolute)
+    }
+
+    /// Executes one instruction. Returns `Ok(false)` once the program has finished.
+    pub fn step(&mut self) -> Result<bool, BfError> {
+        self.paused_at_breakpoint = false;
+        self.interpreter.step()
+    }
+
+    /// Steps until a breakpoint is hit, `max_steps` instructions have run, or the
+    /// program finishes. Returns whether the program has more instructions left.
+    pub fn run_until_breakpoint(&mut self, max_steps: usize) -> Result<bool, BfError> {
+        for i in 0..max_steps {
+            if !self.interpreter.has_more_instructions() {
+                return Ok(false);
+            }
+            if i == 0 && self.paused_at_breakpoint {
+                self.paused_at_breakpoint = false;
+            } else if self.at_breakpoint() {
+                self.paused_at_breakpoint = true;
+                return Ok(true);
+            }
+            if !self.interpreter.step()? {
+                return Ok(false);
+            }
+        }
+        Ok(self.interpreter.has_more_instructions())
+    }
+
+    fn at_breakpoint(&self) -> bool {
+        self.bracket_positions
+            .get(&self.instruction_pointer())
+            .is_some_and(|pos| self.breakpoints.contains(pos))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn run_until_breakpoint_advances_past_a_repeated_hit() {
+        // The `]` at source position 5 is re-hit on every loop iteration; each call
+        // should run one more iteration of `[-]` rather than re-stopping in place.
+        let mut debugger = Debugger::new("+++[-]", Cursor::new(Vec::new()), Vec::new()).unwrap();
+        debugger.break_at(5);
+
+        let cell = |d: &Debugger<_, _>| d.memory_window(0).1[0];
+
+        debugger.run_until_breakpoint(usize::MAX).unwrap();
+        assert_eq!(cell(&debugger), 2);
+        debugger.run_until_breakpoint(usize::MAX).unwrap();
+        assert_eq!(cell(&debugger), 1);
+        debugger.run_until_breakpoint(usize::MAX).unwrap();
+        assert_eq!(cell(&debugger), 0);
+    }
+}
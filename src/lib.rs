@@ -0,0 +1,509 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+
+#[cfg(not(feature = "std"))]
+use alloc::vec;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use core::fmt;
+
+#[cfg(feature = "std")]
+use std::collections::BTreeMap;
+#[cfg(not(feature = "std"))]
+use alloc::collections::BTreeMap;
+
+#[cfg(feature = "std")]
+mod debug;
+mod optimize;
+mod options;
+
+#[cfg(feature = "std")]
+pub use debug::Debugger;
+pub use optimize::optimize;
+pub use options::{CellWidth, EofPolicy, InterpreterOptions, PointerOverflow, TapeMode};
+
+const MEMORY_INIT_ALLOCATE: usize = 1024;
+const MEMORY_DYN_ALLOCATE: usize = 128;
+
+/// An error produced while compiling or running a Brainfuck program.
+#[derive(Debug)]
+pub enum BfError {
+    /// A `[` with no matching `]`.
+    UnmatchedOpen,
+    /// A `]` with no matching `[`.
+    UnmatchedClose,
+    /// The memory pointer moved outside the tape: below cell zero, or past the end of
+    /// a `Fixed` tape (any access to a zero-size `Fixed` tape also reports this).
+    PointerUnderflow,
+    /// Reading from the input stream or writing to the output stream failed.
+    #[cfg(feature = "std")]
+    Io(std::io::Error),
+    /// An I/O failure reported by an embedder-supplied `ByteRead`/`ByteWrite` impl.
+    /// Used under `no_std`, where there's no `std::io::Error` to wrap and the embedder
+    /// (e.g. a UART driver) reports failures as a plain message instead.
+    #[cfg(not(feature = "std"))]
+    IoError(&'static str),
+}
+
+impl fmt::Display for BfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BfError::UnmatchedOpen => write!(f, "unmatched ["),
+            BfError::UnmatchedClose => write!(f, "unmatched ]"),
+            BfError::PointerUnderflow => write!(f, "memory pointer moved outside the tape"),
+            #[cfg(feature = "std")]
+            BfError::Io(e) => write!(f, "io error: {e}"),
+            #[cfg(not(feature = "std"))]
+            BfError::IoError(message) => write!(f, "io error: {message}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BfError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BfError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for BfError {
+    fn from(e: std::io::Error) -> Self {
+        BfError::Io(e)
+    }
+}
+
+/// A single byte source. Implemented for every `std::io::Read` when the `std` feature is
+/// on; without `std` the embedder provides it directly (e.g. a UART), reporting failures
+/// as `BfError::IoError`. Returns `Ok(None)` once the stream is exhausted so `,` can apply
+/// the configured `EofPolicy`.
+pub trait ByteRead {
+    fn read_byte(&mut self) -> Result<Option<u8>, BfError>;
+}
+
+/// A single byte sink. Implemented for every `std::io::Write` when the `std` feature is
+/// on; without `std` the embedder provides it directly, reporting failures as
+/// `BfError::IoError`.
+pub trait ByteWrite {
+    fn write_byte(&mut self, byte: u8) -> Result<(), BfError>;
+}
+
+#[cfg(feature = "std")]
+impl<R: std::io::Read> ByteRead for R {
+    fn read_byte(&mut self) -> Result<Option<u8>, BfError> {
+        let mut byte = [0u8; 1];
+        match self.read(&mut byte) {
+            Ok(0) => Ok(None),
+            Ok(_) => Ok(Some(byte[0])),
+            Err(e) => Err(BfError::Io(e)),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: std::io::Write> ByteWrite for W {
+    fn write_byte(&mut self, byte: u8) -> Result<(), BfError> {
+        self.write_all(&[byte]).map_err(BfError::Io)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Instruction {
+    Right,
+    Left,
+    Increment,
+    Decrement,
+    Print,
+    Read,
+    BeginLoop(usize),
+    EndLoop(usize),
+    /// Adds `delta` to the current cell, wrapping at the configured cell width.
+    /// Folds a run of `+`/`-`.
+    Add(i32),
+    /// Moves the memory pointer by `delta` cells in one step. Folds a run of `>`/`<`.
+    Move(isize),
+    /// Sets the current cell to zero. Replaces a `[-]`/`[+]` clear loop.
+    SetZero,
+    /// Adds `factor` times the current cell to the cell at `offset` from it, wrapping
+    /// at the configured cell width. Emitted for multiply/copy loops, always
+    /// immediately before a `SetZero` and guarded by the `BeginLoop` that precedes
+    /// the whole group.
+    MultAdd { offset: isize, factor: i32 },
+}
+
+pub type CompiledCode = Vec<Instruction>;
+
+/// Parses Brainfuck source into `Instruction`s, resolving `[`/`]` pairs into jump targets,
+/// and records the source position of every `[`/`]`, keyed by its instruction index.
+fn parse(source: &str) -> Result<(CompiledCode, BTreeMap<usize, usize>), BfError> {
+    let mut compiled: CompiledCode = Vec::new();
+    let mut loop_stack = Vec::new();
+    let mut bracket_positions = BTreeMap::new();
+    for (source_pos, char) in source.char_indices() {
+        match char {
+            '>' => compiled.push(Instruction::Right),
+            '<' => compiled.push(Instruction::Left),
+            '+' => compiled.push(Instruction::Increment),
+            '-' => compiled.push(Instruction::Decrement),
+            '.' => compiled.push(Instruction::Print),
+            ',' => compiled.push(Instruction::Read),
+            '[' => {
+                compiled.push(Instruction::BeginLoop(0));
+                let index = compiled.len() - 1;
+                loop_stack.push(index);
+                bracket_positions.insert(index, source_pos);
+            }
+            ']' => {
+                let loop_start = loop_stack.pop().ok_or(BfError::UnmatchedClose)?;
+                compiled.push(Instruction::EndLoop(loop_start));
+                let end = compiled.len() - 1;
+                bracket_positions.insert(end, source_pos);
+                compiled[loop_start] = Instruction::BeginLoop(end);
+            }
+            _ => {}
+        }
+    }
+    if !loop_stack.is_empty() {
+        return Err(BfError::UnmatchedOpen);
+    }
+    Ok((compiled, bracket_positions))
+}
+
+/// Parses Brainfuck source into `Instruction`s, resolving `[`/`]` pairs into jump targets.
+pub fn compile(source: &str) -> Result<CompiledCode, BfError> {
+    let (compiled, _) = parse(source)?;
+    Ok(optimize(compiled))
+}
+
+/// A Brainfuck interpreter over an arbitrary input/output pair, so it can be driven
+/// by real streams in a binary, by in-memory buffers in tests, or by raw byte hooks
+/// on a `no_std` target.
+pub struct Interpreter<R, W> {
+    memory: Vec<u32>,
+    memory_pointer: usize,
+    code: CompiledCode,
+    instruction_pointer: usize,
+    input: R,
+    output: W,
+    options: InterpreterOptions,
+}
+
+impl<R: ByteRead, W: ByteWrite> Interpreter<R, W> {
+    /// Builds an interpreter with this crate's original behavior: a dynamically
+    /// growing tape of wrapping 8-bit cells.
+    pub fn new(code: CompiledCode, input: R, output: W) -> Interpreter<R, W> {
+        Interpreter::with_options(code, input, output, InterpreterOptions::default())
+    }
+
+    pub fn with_options(
+        code: CompiledCode,
+        input: R,
+        output: W,
+        options: InterpreterOptions,
+    ) -> Interpreter<R, W> {
+        let initial_len = match options.tape {
+            TapeMode::Dynamic => MEMORY_INIT_ALLOCATE,
+            TapeMode::Fixed(size) => size,
+        };
+        Interpreter {
+            memory: vec![0; initial_len],
+            memory_pointer: 0,
+            code,
+            instruction_pointer: 0,
+            input,
+            output,
+            options,
+        }
+    }
+
+    fn current_memory(&self) -> u32 {
+        self.memory[self.memory_pointer]
+    }
+
+    fn next_instruction(&self) -> &Instruction {
+        self.code.get(self.instruction_pointer).unwrap()
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn instruction_pointer(&self) -> usize {
+        self.instruction_pointer
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn memory_pointer(&self) -> usize {
+        self.memory_pointer
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn memory(&self) -> &[u32] {
+        &self.memory
+    }
+
+    pub(crate) fn has_more_instructions(&self) -> bool {
+        self.instruction_pointer < self.code.len()
+    }
+
+    /// Moves the memory pointer to an absolute cell, as if by the right number of
+    /// `>`/`<`, subject to the same tape growth and overflow rules.
+    #[cfg(feature = "std")]
+    pub(crate) fn set_memory_pointer(&mut self, absolute: usize) -> Result<(), BfError> {
+        let delta = absolute as isize - self.memory_pointer as isize;
+        move_pointer(self, delta)
+    }
+
+    /// Runs the compiled program to completion.
+    pub fn run(&mut self) -> Result<(), BfError> {
+        while self.step()? {}
+        Ok(())
+    }
+
+    /// Executes a single instruction. Returns `Ok(false)` once the program has run off
+    /// the end of the compiled code, so a debugger can drive the interpreter one step
+    /// at a time instead of only through the monolithic `run` loop.
+    pub fn step(&mut self) -> Result<bool, BfError> {
+        if !self.has_more_instructions() {
+            return Ok(false);
+        }
+        match *self.next_instruction() {
+            Instruction::Right => move_pointer(self, 1)?,
+            Instruction::Left => move_pointer(self, -1)?,
+            Instruction::Increment => add_at(self, 0, 1)?,
+            Instruction::Decrement => add_at(self, 0, -1)?,
+            Instruction::Print => output(self)?,
+            Instruction::Read => input(self)?,
+            Instruction::BeginLoop(loop_end) => {
+                if self.current_memory() == 0 {
+                    self.instruction_pointer = loop_end;
+                }
+            }
+            Instruction::EndLoop(loop_start) => {
+                if self.current_memory() != 0 {
+                    self.instruction_pointer = loop_start;
+                }
+            }
+            Instruction::Add(delta) => add_at(self, 0, delta)?,
+            Instruction::Move(delta) => move_pointer(self, delta)?,
+            Instruction::SetZero => self.memory[self.memory_pointer] = 0,
+            Instruction::MultAdd { offset, factor } => {
+                let mask = self.options.cell_width.mask();
+                let product = self.current_memory().wrapping_mul(factor as u32) & mask;
+                add_at(self, offset, product as i32)?;
+            }
+        }
+        self.instruction_pointer += 1;
+        Ok(self.has_more_instructions())
+    }
+}
+
+/// Resolves `offset` cells from the current memory pointer according to the
+/// configured `TapeMode`/`PointerOverflow`, growing a `Dynamic` tape if the target is
+/// beyond it. Returns an error if the target is out of bounds and overflow is `Abort`.
+fn target_index<R: ByteRead, W: ByteWrite>(
+    context: &mut Interpreter<R, W>,
+    offset: isize,
+) -> Result<usize, BfError> {
+    let target = context.memory_pointer as isize + offset;
+    match context.options.tape {
+        TapeMode::Dynamic => {
+            if target >= 0 {
+                let target = target as usize;
+                if target >= context.memory.len() {
+                    context.memory.resize(target + MEMORY_DYN_ALLOCATE, 0);
+                }
+                return Ok(target);
+            }
+            match context.options.overflow {
+                PointerOverflow::Abort => Err(BfError::PointerUnderflow),
+                PointerOverflow::Wrap => Ok(target.rem_euclid(context.memory.len() as isize) as usize),
+            }
+        }
+        TapeMode::Fixed(size) => {
+            if size == 0 {
+                // Nothing to wrap around to either; every access to an empty tape is out
+                // of bounds.
+                return Err(BfError::PointerUnderflow);
+            }
+            if target >= 0 && (target as usize) < size {
+                return Ok(target as usize);
+            }
+            match context.options.overflow {
+                PointerOverflow::Abort => Err(BfError::PointerUnderflow),
+                PointerOverflow::Wrap => Ok(target.rem_euclid(size as isize) as usize),
+            }
+        }
+    }
+}
+
+fn move_pointer<R: ByteRead, W: ByteWrite>(
+    context: &mut Interpreter<R, W>,
+    delta: isize,
+) -> Result<(), BfError> {
+    context.memory_pointer = target_index(context, delta)?;
+    Ok(())
+}
+
+fn add_at<R: ByteRead, W: ByteWrite>(
+    context: &mut Interpreter<R, W>,
+    offset: isize,
+    delta: i32,
+) -> Result<(), BfError> {
+    let index = target_index(context, offset)?;
+    let mask = context.options.cell_width.mask();
+    context.memory[index] = context.memory[index].wrapping_add(delta as u32) & mask;
+    Ok(())
+}
+
+fn input<R: ByteRead, W: ByteWrite>(context: &mut Interpreter<R, W>) -> Result<(), BfError> {
+    match context.input.read_byte()? {
+        Some(byte) => context.memory[context.memory_pointer] = byte as u32,
+        None => match context.options.eof {
+            EofPolicy::Unchanged => {}
+            EofPolicy::Zero => context.memory[context.memory_pointer] = 0,
+            EofPolicy::AllOnes => {
+                context.memory[context.memory_pointer] = context.options.cell_width.mask()
+            }
+        },
+    }
+    Ok(())
+}
+
+fn output<R: ByteRead, W: ByteWrite>(context: &mut Interpreter<R, W>) -> Result<(), BfError> {
+    context.output.write_byte((context.current_memory() & 0xFF) as u8)
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn run(source: &str, input: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        let compiled = compile(source).unwrap();
+        Interpreter::new(compiled, Cursor::new(input.to_vec()), &mut out)
+            .run()
+            .unwrap();
+        out
+    }
+
+    fn run_unoptimized(source: &str, input: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        let (compiled, _) = parse(source).unwrap();
+        Interpreter::new(compiled, Cursor::new(input.to_vec()), &mut out)
+            .run()
+            .unwrap();
+        out
+    }
+
+    #[test]
+    fn hello_world() {
+        let source = "++++++++[>++++[>++>+++>+++>+<<<<-]>+>+>->>+[<]<-]>>.>---.+++++++..+++.>>.<-.<.+++.------.--------.>>+.>++.";
+        assert_eq!(run(source, &[]), b"Hello World!\n");
+    }
+
+    #[test]
+    fn optimized_and_unoptimized_agree_on_a_multiply_loop() {
+        // Multiplies the 4 in the starting cell by 3 into the next cell over via a
+        // multiply loop, which also leaves the base cell at zero.
+        let source = "++++[>+++<-]>.";
+        assert_eq!(run(source, &[]), run_unoptimized(source, &[]));
+        assert_eq!(run(source, &[]), [12]);
+    }
+
+    #[test]
+    fn unmatched_brackets_are_errors() {
+        assert!(matches!(compile("[").unwrap_err(), BfError::UnmatchedOpen));
+        assert!(matches!(compile("]").unwrap_err(), BfError::UnmatchedClose));
+    }
+
+    #[test]
+    fn pointer_underflow_is_an_error_not_a_panic() {
+        let compiled = compile("<").unwrap();
+        let mut out = Vec::new();
+        let mut interpreter = Interpreter::new(compiled, Cursor::new(Vec::new()), &mut out);
+        assert!(matches!(interpreter.run(), Err(BfError::PointerUnderflow)));
+    }
+
+    #[test]
+    fn eof_policy_sets_the_cell() {
+        let options = InterpreterOptions {
+            eof: EofPolicy::AllOnes,
+            ..InterpreterOptions::default()
+        };
+        let compiled = compile(",.").unwrap();
+        let mut out = Vec::new();
+        Interpreter::with_options(compiled, Cursor::new(Vec::new()), &mut out, options)
+            .run()
+            .unwrap();
+        assert_eq!(out, [0xFF]);
+    }
+
+    #[test]
+    fn fixed_tape_aborts_on_overflow() {
+        let options = InterpreterOptions {
+            tape: TapeMode::Fixed(3),
+            overflow: PointerOverflow::Abort,
+            ..InterpreterOptions::default()
+        };
+        // Fixed(3) only has cells 0..3; the third `>` walks off the end.
+        let compiled = compile(">>>").unwrap();
+        let mut out = Vec::new();
+        let mut interpreter =
+            Interpreter::with_options(compiled, Cursor::new(Vec::new()), &mut out, options);
+        assert!(matches!(interpreter.run(), Err(BfError::PointerUnderflow)));
+    }
+
+    #[test]
+    fn fixed_tape_wraps_to_the_other_end() {
+        let options = InterpreterOptions {
+            tape: TapeMode::Fixed(5),
+            overflow: PointerOverflow::Wrap,
+            ..InterpreterOptions::default()
+        };
+        // Moving left from cell 0 on a Fixed(5) tape wraps to cell 4.
+        let compiled = compile("<").unwrap();
+        let mut out = Vec::new();
+        let mut interpreter =
+            Interpreter::with_options(compiled, Cursor::new(Vec::new()), &mut out, options);
+        interpreter.run().unwrap();
+        assert_eq!(interpreter.memory_pointer(), 4);
+    }
+
+    #[test]
+    fn fixed_zero_size_tape_is_an_error_not_a_panic() {
+        let options = InterpreterOptions {
+            tape: TapeMode::Fixed(0),
+            overflow: PointerOverflow::Wrap,
+            ..InterpreterOptions::default()
+        };
+        let compiled = compile("+").unwrap();
+        let mut out = Vec::new();
+        let mut interpreter =
+            Interpreter::with_options(compiled, Cursor::new(Vec::new()), &mut out, options);
+        assert!(matches!(interpreter.run(), Err(BfError::PointerUnderflow)));
+    }
+
+    #[test]
+    fn multiply_loop_mask_math_holds_at_u16() {
+        // Base cell starts at 10; the multiply loop adds 30 per iteration to the next
+        // cell, for a product of 300 — beyond u8 range, so this only comes out right if
+        // `MultAdd`/`add_at` mask against the configured `CellWidth` rather than u8.
+        let source = "++++++++++[>".to_string() + &"+".repeat(30) + "<-]";
+        let options = InterpreterOptions {
+            cell_width: CellWidth::U16,
+            ..InterpreterOptions::default()
+        };
+        let compiled = compile(&source).unwrap();
+        let mut out = Vec::new();
+        let mut interpreter =
+            Interpreter::with_options(compiled, Cursor::new(Vec::new()), &mut out, options);
+        interpreter.run().unwrap();
+        assert_eq!(interpreter.memory()[0], 0);
+        assert_eq!(interpreter.memory()[1], 300);
+    }
+}
@@ -1,159 +1,154 @@
 use std::fs;
-use std::str::FromStr;
+use std::io::{self, BufRead};
+use std::process::ExitCode;
 
-const MEMORY_INIT_ALLOCATE: usize = 1024;
-const MEMORY_DYN_ALLOCATE: usize = 128;
+use brainfuck_rust::{compile, BfError, Debugger, Interpreter};
 
-enum Instruction {
-    Right,
-    Left,
-    Increment,
-    Decrement,
-    Print,
-    Read,
-    BeginLoop(usize),
-    EndLoop(usize),
-}
-
-type CompiledCode = Vec<Instruction>;
-
-struct Interpreter {
-    memory: Vec<u8>,
-    memory_pointer: usize,
-    code: CompiledCode,
-    instruction_pointer: usize,
-}
-
-impl Interpreter {
-    fn new(code: CompiledCode) -> Interpreter {
-        Interpreter {
-            memory: vec![0; MEMORY_INIT_ALLOCATE],
-            memory_pointer: 0,
-            code: code,
-            instruction_pointer: 0,
-        }
-    }
-
-    fn current_memory(&self) -> u8 {
-        self.memory[self.memory_pointer]
-    }
-
-    fn next_instruction(&self) -> &Instruction {
-        self.code.get(self.instruction_pointer).unwrap()
-    }
-}
-
-fn right(context: &mut Interpreter) {
-    context.memory_pointer += 1;
-    if context.memory_pointer >= context.memory.len() {
-        context.memory.resize(context.memory.len() + MEMORY_DYN_ALLOCATE, 0);
-    }
-}
-
-fn left(context: &mut Interpreter) {
-    if context.memory_pointer == 0 {
-        panic!("Inaccessible memory");
+fn main() -> ExitCode {
+    let args = std::env::args().collect::<Vec<_>>();
+    if args.len() == 1 {
+        println!(
+            "Usage:\n\t <code> \n\t -f <file> \n\t --file <file> \n\t --debug <code> \n\t --debug -f <file>"
+        );
+        return ExitCode::SUCCESS;
     }
-    context.memory_pointer -= 1;
-}
 
-fn increment(context: &mut Interpreter) {
-    if context.current_memory() == 255 {
-        context.memory[context.memory_pointer] = 0;
-    } else {
-        context.memory[context.memory_pointer] += 1;
-    }
-}
+    let debug = args.get(1).map(String::as_str) == Some("--debug");
+    // extract_code never reads args[1] (it only looks at args[2..]), so "--debug" can sit
+    // in that slot unshifted and the -f/--file parsing lines up exactly as it does for a
+    // plain, non-debug invocation.
+    let code = match extract_code(&args) {
+        Ok(code) => code,
+        Err(message) => {
+            println!("{message}");
+            return ExitCode::FAILURE;
+        }
+    };
 
-fn decrement(context: &mut Interpreter) {
-    if context.current_memory() == 0 {
-        context.memory[context.memory_pointer] = 255;
+    if debug {
+        run_debugger(&code)
     } else {
-        context.memory[context.memory_pointer] -= 1;
+        run(&code)
     }
 }
 
-fn input(context: &mut Interpreter) {
-    let mut input = String::new();
-    std::io::stdin().read_line(&mut input).unwrap();
-    context.memory[context.memory_pointer] = input.trim().chars().next().expect("Expected ascii char") as u8;
-}
-
-fn output(context: &mut Interpreter) {
-    print!("{}", context.current_memory() as char);
-}
-
-
-fn main() {
-    let args = std::env::args().collect::<Vec<_>>();
-    if args.len() == 1 {
-        println!("Usage:\n\t <code> \n\t -f <file> \n\t --file <file>");
-        return;
-    }
-    let mut code;
+fn extract_code(args: &[String]) -> Result<String, &'static str> {
     if args.len() >= 3 && (args[2] == "-f" || args[2] == "--file") {
         if args.len() == 3 {
-            println!("No file specified");
-            return;
+            return Err("No file specified");
         }
-        let filename = &args[3];
-        code = fs::read_to_string(filename).expect("Something went wrong reading the file");
-    }
-    else {
-        code = String::from_str(&args[2..].join(" ")).expect("Enter valid code");
+        fs::read_to_string(&args[3]).map_err(|_| "Something went wrong reading the file")
+    } else {
+        Ok(args[2..].join(" "))
     }
-    code.retain(|c| "<>+-.,[]".contains(c));
+}
 
-    let mut compiled: CompiledCode = Vec::new();
-    let mut loop_stack = Vec::new();
-    let mut index = 0;
-    for char in code.chars() {
-        index += 1;
-        match char {
-            '>' => compiled.push(Instruction::Right),
-            '<' => compiled.push(Instruction::Left),
-            '+' => compiled.push(Instruction::Increment),
-            '-' => compiled.push(Instruction::Decrement),
-            '.' => compiled.push(Instruction::Print),
-            ',' => compiled.push(Instruction::Read),
-            '[' => {
-                compiled.push(Instruction::BeginLoop(0));
-                loop_stack.push(index);
-            },
-            ']' => {
-                let loop_start = loop_stack.pop().expect("Unmatched ]");
-                compiled[loop_start - 1] = Instruction::BeginLoop(index);
-                compiled.push(Instruction::EndLoop(loop_start));
-            },
-            _ => {index -= 1}
+fn run(code: &str) -> ExitCode {
+    match compile(code) {
+        Ok(compiled) => {
+            let mut interpreter = Interpreter::new(compiled, io::stdin(), io::stdout());
+            if let Err(e) = interpreter.run() {
+                report(e);
+                return ExitCode::FAILURE;
+            }
+        }
+        Err(e) => {
+            report(e);
+            return ExitCode::FAILURE;
         }
     }
-    if !loop_stack.is_empty() {
-        panic!("Unmatched [");
-    }
-
-    let mut interpreter = Interpreter::new(compiled);
+    ExitCode::SUCCESS
+}
 
-    while interpreter.instruction_pointer < interpreter.code.len() {
-        match interpreter.next_instruction() {
-            Instruction::Right => right(&mut interpreter),
-            Instruction::Left => left(&mut interpreter),
-            Instruction::Increment => increment(&mut interpreter),
-            Instruction::Decrement => decrement(&mut interpreter),
-            Instruction::Print => output(&mut interpreter),
-            Instruction::Read => input(&mut interpreter),
-            Instruction::BeginLoop(loop_end) => {
-                if interpreter.current_memory() == 0 {
-                    interpreter.instruction_pointer = loop_end - 0;
+/// A line-oriented stepping debugger: `step [n]`, `continue`, `break <source pos>`,
+/// `dump-memory [radius]`, `set-pointer <cell>`, `quit`.
+fn run_debugger(code: &str) -> ExitCode {
+    let mut debugger = match Debugger::new(code, io::stdin(), io::stdout()) {
+        Ok(d) => d,
+        Err(e) => {
+            report(e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    println!("Stepping debugger. Commands: step [n], continue, break <pos>, dump-memory [radius], set-pointer <n>, quit");
+    print_state(&debugger);
+
+    let stdin = io::stdin();
+    for line in stdin.lock().lines() {
+        let Ok(line) = line else { break };
+        let mut words = line.split_whitespace();
+        match words.next() {
+            Some("step") => {
+                let steps: usize = words.next().and_then(|w| w.parse().ok()).unwrap_or(1);
+                match debugger.run_until_breakpoint(steps) {
+                    Ok(more) => {
+                        print_state(&debugger);
+                        if !more {
+                            println!("program finished");
+                            break;
+                        }
+                    }
+                    Err(e) => {
+                        report(e);
+                        return ExitCode::FAILURE;
+                    }
                 }
             }
-            Instruction::EndLoop(loop_start) => {
-                if interpreter.current_memory() != 0 {
-                    interpreter.instruction_pointer = loop_start - 1;
+            Some("continue") => match debugger.run_until_breakpoint(usize::MAX) {
+                Ok(more) => {
+                    print_state(&debugger);
+                    if !more {
+                        println!("program finished");
+                        break;
+                    }
                 }
+                Err(e) => {
+                    report(e);
+                    return ExitCode::FAILURE;
+                }
+            },
+            Some("break") => match words.next().and_then(|w| w.parse().ok()) {
+                Some(pos) => debugger.break_at(pos),
+                None => println!("usage: break <source position>"),
+            },
+            Some("dump-memory") => {
+                let radius: usize = words.next().and_then(|w| w.parse().ok()).unwrap_or(8);
+                print_memory(&debugger, radius);
             }
+            Some("set-pointer") => match words.next().and_then(|w| w.parse().ok()) {
+                Some(cell) => {
+                    if let Err(e) = debugger.set_memory_pointer(cell) {
+                        report(e);
+                    }
+                }
+                None => println!("usage: set-pointer <cell>"),
+            },
+            Some("quit") | Some("exit") => break,
+            _ => println!("commands: step [n], continue, break <pos>, dump-memory [radius], set-pointer <n>, quit"),
         }
-        interpreter.instruction_pointer += 1;
     }
+    ExitCode::SUCCESS
+}
+
+fn print_state(debugger: &Debugger<io::Stdin, io::Stdout>) {
+    println!(
+        "ip={} mp={}",
+        debugger.instruction_pointer(),
+        debugger.memory_pointer()
+    );
+}
+
+fn print_memory(debugger: &Debugger<io::Stdin, io::Stdout>, radius: usize) {
+    let pointer = debugger.memory_pointer();
+    let (start, window) = debugger.memory_window(radius);
+    for (i, cell) in window.iter().enumerate() {
+        let index = start + i;
+        let marker = if index == pointer { "*" } else { " " };
+        println!("{marker} [{index}] = {cell}");
+    }
+}
 
+fn report(e: BfError) {
+    eprintln!("error: {e}");
 }
@@ -0,0 +1,137 @@
+use crate::{CompiledCode, Instruction};
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+/// Rewrites freshly compiled code into a denser, faster instruction stream:
+/// runs of `+`/`-` and `>`/`<` are folded into single counted ops, `[-]`/`[+]`
+/// clear loops become `SetZero`, and multiply/copy loops become `MultAdd` + `SetZero`.
+pub fn optimize(code: CompiledCode) -> CompiledCode {
+    fuse_loops(fold_runs(code))
+}
+
+fn fold_runs(code: CompiledCode) -> Vec<Instruction> {
+    let mut out = Vec::with_capacity(code.len());
+    let mut iter = code.into_iter().peekable();
+    while let Some(instruction) = iter.next() {
+        match instruction {
+            Instruction::Increment | Instruction::Decrement => {
+                let mut delta: i32 = if matches!(instruction, Instruction::Increment) {
+                    1
+                } else {
+                    -1
+                };
+                while let Some(next) = iter.peek() {
+                    match next {
+                        Instruction::Increment => delta += 1,
+                        Instruction::Decrement => delta -= 1,
+                        _ => break,
+                    }
+                    iter.next();
+                }
+                if delta != 0 {
+                    out.push(Instruction::Add(delta));
+                }
+            }
+            Instruction::Right | Instruction::Left => {
+                let mut delta: isize = if matches!(instruction, Instruction::Right) {
+                    1
+                } else {
+                    -1
+                };
+                while let Some(next) = iter.peek() {
+                    match next {
+                        Instruction::Right => delta += 1,
+                        Instruction::Left => delta -= 1,
+                        _ => break,
+                    }
+                    iter.next();
+                }
+                if delta != 0 {
+                    out.push(Instruction::Move(delta));
+                }
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// Walks the folded code, collapsing each balanced loop whose body matches a
+/// recognized shape (clear loop or multiply/copy loop) and relinking the jump
+/// targets of the loops that are left as-is.
+fn fuse_loops(code: Vec<Instruction>) -> CompiledCode {
+    let mut out: CompiledCode = Vec::with_capacity(code.len());
+    let mut loop_starts: Vec<usize> = Vec::new();
+    for instruction in code {
+        match instruction {
+            Instruction::BeginLoop(_) => {
+                loop_starts.push(out.len());
+                out.push(Instruction::BeginLoop(0));
+            }
+            Instruction::EndLoop(_) => {
+                let start = loop_starts.pop().expect("unmatched loop during optimization");
+                let body = out.split_off(start + 1);
+                if is_clear_loop(&body) {
+                    out.truncate(start);
+                    out.push(Instruction::SetZero);
+                } else if let Some(mult_adds) = as_multiply_loop(&body) {
+                    out.truncate(start);
+                    let begin = out.len();
+                    out.push(Instruction::BeginLoop(0));
+                    out.extend(mult_adds.into_iter().map(|(offset, factor)| Instruction::MultAdd { offset, factor }));
+                    out.push(Instruction::SetZero);
+                    // The guard jumps to the `SetZero`, so the step's `+= 1` lands just past it.
+                    let end = out.len() - 1;
+                    out[begin] = Instruction::BeginLoop(end);
+                } else {
+                    out.extend(body);
+                    let end = out.len();
+                    out[start] = Instruction::BeginLoop(end);
+                    out.push(Instruction::EndLoop(start));
+                }
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// `[-]` or `[+]`: a single-instruction body that flips the current cell by exactly one.
+fn is_clear_loop(body: &[Instruction]) -> bool {
+    matches!(body, [Instruction::Add(1)] | [Instruction::Add(-1)])
+}
+
+/// A loop body made only of `Add`/`Move` that returns the pointer to where it started and
+/// decrements the base cell by exactly one per iteration is equivalent to distributing the
+/// base cell's value, scaled per offset, to the other cells it touches.
+fn as_multiply_loop(body: &[Instruction]) -> Option<Vec<(isize, i32)>> {
+    let mut deltas: Vec<(isize, i32)> = Vec::new();
+    let mut offset: isize = 0;
+    for instruction in body {
+        match *instruction {
+            Instruction::Add(d) => {
+                if let Some(entry) = deltas.iter_mut().find(|(o, _)| *o == offset) {
+                    entry.1 += d;
+                } else {
+                    deltas.push((offset, d));
+                }
+            }
+            Instruction::Move(m) => offset += m,
+            _ => return None,
+        }
+    }
+    if offset != 0 {
+        return None;
+    }
+    let base = deltas.iter().position(|(o, _)| *o == 0)?;
+    // Must be exactly -1, not merely congruent to it mod 256: `compile`/`optimize` don't
+    // know the `CellWidth` the code will run under, so a loop that nets e.g. +255 at the
+    // base cell (only -1 mod 256) would otherwise be misidentified at wider cell widths.
+    if deltas[base].1 != -1 {
+        return None;
+    }
+    deltas.remove(base);
+    deltas.retain(|(_, d)| *d != 0);
+    Some(deltas)
+}
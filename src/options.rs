@@ -0,0 +1,64 @@
+/// How the tape grows (or doesn't) as the memory pointer moves.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TapeMode {
+    /// Starts small and grows to the right on demand, as this crate always has.
+    #[default]
+    Dynamic,
+    /// A tape of exactly `0..size`, as the classic "30000 cells" convention assumes.
+    Fixed(usize),
+}
+
+/// What happens when the memory pointer would move outside the tape: left of cell
+/// zero always, or past the end of a `Fixed` tape.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PointerOverflow {
+    /// Fail the run with `BfError::PointerUnderflow`.
+    #[default]
+    Abort,
+    /// Wrap around to the other end of the (current, for `Dynamic`) tape.
+    Wrap,
+}
+
+/// The integer width of a single cell. Arithmetic always wraps at this width,
+/// regardless of how the tape is stored.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CellWidth {
+    #[default]
+    U8,
+    U16,
+    U32,
+}
+
+impl CellWidth {
+    pub(crate) fn mask(self) -> u32 {
+        match self {
+            CellWidth::U8 => 0xFF,
+            CellWidth::U16 => 0xFFFF,
+            CellWidth::U32 => 0xFFFF_FFFF,
+        }
+    }
+}
+
+/// What a `,` stores in the current cell once the input stream is exhausted. This is
+/// the classic source of portability bugs between Brainfuck implementations.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EofPolicy {
+    /// Leave the current cell as it was.
+    #[default]
+    Unchanged,
+    /// Set the current cell to 0.
+    Zero,
+    /// Set every bit of the current cell (255 / 65535 / 4294967295 depending on width).
+    AllOnes,
+}
+
+/// Tunable behavior for an `Interpreter`. The defaults match this crate's original,
+/// hardwired behavior: a dynamically growing tape of wrapping 8-bit cells that aborts
+/// on pointer underflow and leaves the cell unchanged at EOF.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InterpreterOptions {
+    pub tape: TapeMode,
+    pub overflow: PointerOverflow,
+    pub cell_width: CellWidth,
+    pub eof: EofPolicy,
+}